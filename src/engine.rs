@@ -1,6 +1,6 @@
-use anyhow::Result;
 use std::collections::HashMap;
 
+use crate::error::EngineError;
 use crate::models::{account::Account, transaction::Transaction};
 
 pub struct Engine {
@@ -14,7 +14,7 @@ impl Engine {
         }
     }
 
-    pub fn process_transaction(&mut self, transaction: Transaction) -> Result<()> {
+    pub fn process_transaction(&mut self, transaction: Transaction) -> Result<(), EngineError> {
         let client_id = transaction.client();
 
         let account = self
@@ -28,4 +28,16 @@ impl Engine {
     pub fn get_accounts(&self) -> &HashMap<u16, Account> {
         &self.accounts
     }
+
+    /// Consume the engine and hand back its accounts, used when merging the
+    /// per-shard engines of the concurrent runner.
+    pub fn into_accounts(self) -> HashMap<u16, Account> {
+        self.accounts
+    }
+
+    /// Absorb an account owned by another engine. Shards partition clients
+    /// disjointly, so a client is never inserted twice.
+    pub fn insert_account(&mut self, account: Account) {
+        self.accounts.insert(account.client(), account);
+    }
 }
@@ -0,0 +1,36 @@
+use thiserror::Error;
+
+/// Concrete failure modes the engine can surface while applying a
+/// transaction to an account.
+///
+/// Each variant names a specific rejection reason so that callers can match
+/// on and count them rather than parsing free-text error strings.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum EngineError {
+    #[error("insufficient funds")]
+    NotEnoughFunds,
+
+    #[error("unknown transaction {tx}")]
+    UnknownTransaction { tx: u32 },
+
+    #[error("duplicate transaction {tx}")]
+    DuplicateTransaction { tx: u32 },
+
+    #[error("transaction already disputed")]
+    AlreadyDisputed,
+
+    #[error("transaction not disputed")]
+    NotDisputed,
+
+    #[error("account is frozen")]
+    FrozenAccount,
+
+    #[error("client mismatch: expected {expected}, got {got}")]
+    ClientMismatch { expected: u16, got: u16 },
+
+    #[error("amount must not be negative")]
+    NegativeAmount,
+
+    #[error("only deposits and withdrawals can be disputed")]
+    NonDepositDispute,
+}
@@ -3,13 +3,19 @@ use clap::Parser;
 use crate::{engine::Engine, runner::file_runner::FileRunner};
 
 mod engine;
+mod error;
 mod models;
 mod runner;
 
 #[derive(Parser, Debug)]
 #[command(version)]
 struct Args {
-    input_file: String,
+    /// Input CSV path, or `-` to read from stdin. Defaults to stdin when omitted.
+    input_file: Option<String>,
+
+    /// Number of shards to process clients across. `1` keeps the serial path.
+    #[arg(long, default_value_t = 1)]
+    workers: usize,
 }
 
 fn main() {
@@ -19,7 +25,12 @@ fn main() {
 
     let runner = FileRunner::new();
 
-    runner
-        .run(&args.input_file, &mut engine)
-        .expect("Error occured running the engine");
+    let result = match args.input_file.as_deref() {
+        None | Some("-") => {
+            runner.run_reader(std::io::stdin().lock(), &mut engine, args.workers)
+        }
+        Some(path) => runner.run(path, &mut engine, args.workers),
+    };
+
+    result.expect("Error occured running the engine");
 }
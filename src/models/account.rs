@@ -1,30 +1,70 @@
 use std::collections::HashMap;
 
-use anyhow::{Result, anyhow, bail};
 use rust_decimal::Decimal;
 
+use crate::error::EngineError;
 use crate::models::transaction::{Transaction, TransactionType};
 
-pub struct Account {
-    client: u16,
+/// Identifier of an asset/currency a balance is denominated in.
+pub type CurrencyId = String;
+
+/// Currency assumed when a transaction does not name one, preserving the
+/// single-currency behaviour of older inputs.
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+/// The per-currency ledger held by an [`Account`].
+///
+/// The invariant `total == available + withheld` holds after every operation,
+/// except where a disputed deposit legitimately drives `available` negative.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Balances {
     available: Decimal,
     withheld: Decimal,
     total: Decimal,
+}
+
+impl Balances {
+    pub fn available(&self) -> Decimal {
+        self.available
+    }
+
+    pub fn withheld(&self) -> Decimal {
+        self.withheld
+    }
+
+    pub fn total(&self) -> Decimal {
+        self.total
+    }
+}
+
+/// Lifecycle state of an effecting transaction (a deposit or withdrawal)
+/// tracked by an [`Account`].
+///
+/// The legal transitions are `Processed -> Disputed`, `Disputed -> Resolved`
+/// and `Disputed -> ChargedBack`. A resolved transaction is dispute-eligible
+/// again, while a charged-back transaction is terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TxState {
+    Processed,
+    Disputed,
+    Resolved,
+    ChargedBack,
+}
+
+pub struct Account {
+    client: u16,
     locked: bool,
-    effect_transactions: HashMap<u32, Transaction>,
-    dispute_transactions: HashMap<u32, Transaction>,
+    balances: HashMap<CurrencyId, Balances>,
+    transactions: HashMap<u32, (Transaction, TxState)>,
 }
 
 impl Account {
     pub fn new(client: u16) -> Self {
         Account {
             client,
-            available: Decimal::ZERO,
-            withheld: Decimal::ZERO,
-            total: Decimal::ZERO,
             locked: false,
-            effect_transactions: HashMap::new(),
-            dispute_transactions: HashMap::new(),
+            balances: HashMap::new(),
+            transactions: HashMap::new(),
         }
     }
 
@@ -36,129 +76,200 @@ impl Account {
         self.client
     }
 
-    pub fn available(&self) -> Decimal {
-        self.available
-    }
-
-    pub fn withheld(&self) -> Decimal {
-        self.withheld
+    /// Per-currency balances held by this account, one entry per asset the
+    /// account has ever touched.
+    pub fn balances(&self) -> &HashMap<CurrencyId, Balances> {
+        &self.balances
     }
 
-    pub fn total(&self) -> Decimal {
-        self.total
+    /// Balance for a single currency, zeroed if the account has never held it.
+    pub fn balance(&self, currency: &str) -> Balances {
+        self.balances.get(currency).copied().unwrap_or_default()
     }
 
-    pub fn process_transaction(&mut self, transaction: Transaction) -> Result<()> {
+    pub fn process_transaction(&mut self, transaction: Transaction) -> Result<(), EngineError> {
         if transaction.client() != self.client {
-            bail!("Transaction client ID does not match account client ID");
+            return Err(EngineError::ClientMismatch {
+                expected: self.client,
+                got: transaction.client(),
+            });
+        }
+
+        // An effecting transaction whose `tx` id this account already holds is
+        // rejected rather than clobbering the original record, which later
+        // dispute resolution relies on. Ids are checked per account only.
+        if matches!(
+            transaction.transaction_type(),
+            TransactionType::Deposit { .. } | TransactionType::Withdrawal { .. }
+        ) && self.transactions.contains_key(&transaction.tx())
+        {
+            return Err(EngineError::DuplicateTransaction {
+                tx: transaction.tx(),
+            });
         }
 
         match transaction.transaction_type() {
-            TransactionType::Deposit { amount } => self.deposit(*amount)?,
-            TransactionType::Withdrawal { amount } => self.withdraw(*amount)?,
+            TransactionType::Deposit { amount } => {
+                let amount = *amount;
+                self.deposit(transaction.currency(), amount)?;
+                self.transactions
+                    .insert(transaction.tx(), (transaction, TxState::Processed));
+            }
+            TransactionType::Withdrawal { amount } => {
+                let amount = *amount;
+                self.withdraw(transaction.currency(), amount)?;
+                self.transactions
+                    .insert(transaction.tx(), (transaction, TxState::Processed));
+            }
             TransactionType::Dispute => self.dispute(&transaction)?,
             TransactionType::Resolve => self.resolve(&transaction)?,
             TransactionType::Chargeback => self.chargeback(&transaction)?,
         }
 
-        match transaction.transaction_type() {
-            TransactionType::Deposit { .. } | TransactionType::Withdrawal { .. } => {
-                self.effect_transactions
-                    .insert(transaction.tx(), transaction);
-            }
-            _ => {}
-        }
         Ok(())
     }
 
-    fn deposit(&mut self, amount: Decimal) -> Result<()> {
+    pub(crate) fn deposit(&mut self, currency: &str, amount: Decimal) -> Result<(), EngineError> {
         if self.locked {
-            bail!("Account locked");
+            return Err(EngineError::FrozenAccount);
         }
 
         if amount < Decimal::ZERO {
-            bail!("Deposit amount should be positive");
+            return Err(EngineError::NegativeAmount);
         }
 
-        self.available += amount;
-        self.total += amount;
+        let balance = self.balance_mut(currency);
+        balance.available += amount;
+        balance.total += amount;
         Ok(())
     }
 
-    fn withdraw(&mut self, amount: Decimal) -> Result<()> {
+    pub(crate) fn withdraw(&mut self, currency: &str, amount: Decimal) -> Result<(), EngineError> {
         if self.locked {
-            bail!("Account locked");
+            return Err(EngineError::FrozenAccount);
         }
 
         if amount < Decimal::ZERO {
-            bail!("Withdrawed amount should be positive");
+            return Err(EngineError::NegativeAmount);
         }
 
-        if self.available >= amount {
-            self.available -= amount;
-            self.total -= amount;
+        let balance = self.balance_mut(currency);
+        if balance.available >= amount {
+            balance.available -= amount;
+            balance.total -= amount;
             Ok(())
         } else {
-            bail!("Insufficient funds")
+            Err(EngineError::NotEnoughFunds)
         }
     }
 
-    fn dispute(&mut self, tx: &Transaction) -> Result<()> {
-        if self.dispute_transactions.contains_key(&tx.tx()) {
-            bail!("Transaction already disputed");
-        }
-
-        let transaction = self
-            .effect_transactions
+    pub(crate) fn dispute(&mut self, tx: &Transaction) -> Result<(), EngineError> {
+        let (transaction, state) = self
+            .transactions
             .get(&tx.tx())
-            .ok_or_else(|| anyhow!("Transaction not found"))?
-            .clone();
+            .ok_or(EngineError::UnknownTransaction { tx: tx.tx() })?;
+
+        match state {
+            TxState::Processed | TxState::Resolved => {}
+            TxState::Disputed | TxState::ChargedBack => return Err(EngineError::AlreadyDisputed),
+        }
 
         let amount = transaction.get_amount();
+        let currency = transaction.currency().to_string();
 
-        if !matches!(
-            transaction.transaction_type(),
-            TransactionType::Deposit { .. }
-        ) {
-            bail!("Only deposit transactions can be disputed");
+        // Disputing a deposit holds funds that were already available; disputing
+        // a withdrawal claws the reversed outflow back into the ledger and holds
+        // it. Either way `total == available + withheld` is preserved.
+        match transaction.transaction_type() {
+            TransactionType::Deposit { .. } => {
+                let balance = self.balance_mut(&currency);
+                balance.available -= amount;
+                balance.withheld += amount;
+            }
+            TransactionType::Withdrawal { .. } => {
+                let balance = self.balance_mut(&currency);
+                balance.total += amount;
+                balance.withheld += amount;
+            }
+            _ => return Err(EngineError::NonDepositDispute),
         }
 
-        self.effect_transactions.remove(&tx.tx());
-
-        self.available -= amount;
-        self.withheld += amount;
-        self.dispute_transactions
-            .insert(tx.tx(), transaction.clone());
+        if let Some(entry) = self.transactions.get_mut(&tx.tx()) {
+            entry.1 = TxState::Disputed;
+        }
         Ok(())
     }
 
-    fn resolve(&mut self, tx: &Transaction) -> Result<()> {
-        let transaction = self
-            .dispute_transactions
-            .remove(&tx.tx())
-            .ok_or_else(|| anyhow!("Transaction not disputed"))?;
+    pub(crate) fn resolve(&mut self, tx: &Transaction) -> Result<(), EngineError> {
+        let (transaction, state) = self
+            .transactions
+            .get(&tx.tx())
+            .ok_or(EngineError::NotDisputed)?;
+
+        if *state != TxState::Disputed {
+            return Err(EngineError::NotDisputed);
+        }
 
         let amount = transaction.get_amount();
+        let currency = transaction.currency().to_string();
 
-        self.withheld -= amount;
-        self.available += amount;
-        self.effect_transactions.insert(tx.tx(), transaction);
+        // Inverse of `dispute`: releasing a deposit restores availability, while
+        // releasing a withdrawal lets the contested outflow leave again.
+        match transaction.transaction_type() {
+            TransactionType::Withdrawal { .. } => {
+                let balance = self.balance_mut(&currency);
+                balance.withheld -= amount;
+                balance.total -= amount;
+            }
+            _ => {
+                let balance = self.balance_mut(&currency);
+                balance.withheld -= amount;
+                balance.available += amount;
+            }
+        }
+        if let Some(entry) = self.transactions.get_mut(&tx.tx()) {
+            entry.1 = TxState::Resolved;
+        }
         Ok(())
     }
 
-    fn chargeback(&mut self, tx: &Transaction) -> Result<()> {
-        let transaction = self
-            .dispute_transactions
-            .remove(&tx.tx())
-            .ok_or_else(|| anyhow!("Transaction not disputed"))?;
+    pub(crate) fn chargeback(&mut self, tx: &Transaction) -> Result<(), EngineError> {
+        let (transaction, state) = self
+            .transactions
+            .get(&tx.tx())
+            .ok_or(EngineError::NotDisputed)?;
+
+        if *state != TxState::Disputed {
+            return Err(EngineError::NotDisputed);
+        }
 
         let amount = transaction.get_amount();
+        let currency = transaction.currency().to_string();
 
-        self.withheld -= amount;
-        self.total -= amount;
+        // A charged-back deposit removes the held funds from the ledger; a
+        // charged-back withdrawal releases the reclaimed outflow to the client.
+        match transaction.transaction_type() {
+            TransactionType::Withdrawal { .. } => {
+                let balance = self.balance_mut(&currency);
+                balance.withheld -= amount;
+                balance.available += amount;
+            }
+            _ => {
+                let balance = self.balance_mut(&currency);
+                balance.withheld -= amount;
+                balance.total -= amount;
+            }
+        }
         self.locked = true;
+        if let Some(entry) = self.transactions.get_mut(&tx.tx()) {
+            entry.1 = TxState::ChargedBack;
+        }
         Ok(())
     }
+
+    fn balance_mut(&mut self, currency: &str) -> &mut Balances {
+        self.balances.entry(currency.to_string()).or_default()
+    }
 }
 
 #[cfg(test)]
@@ -182,7 +293,7 @@ mod tests {
         let result = account.process_transaction(deposit_tx);
 
         assert!(result.is_err());
-        assert!(account.available == Decimal::ZERO);
+        assert!(account.balance(DEFAULT_CURRENCY).available() == Decimal::ZERO);
     }
 
     #[test]
@@ -199,7 +310,7 @@ mod tests {
         let result = account.process_transaction(deposit_tx);
 
         assert!(result.is_err());
-        assert!(account.available == dec!(0.0));
+        assert!(account.balance(DEFAULT_CURRENCY).available() == dec!(0.0));
     }
 
     #[test]
@@ -215,8 +326,8 @@ mod tests {
 
         account.process_transaction(deposit_tx).unwrap();
 
-        assert_eq!(account.available, dec!(100.01));
-        assert_eq!(account.total, dec!(100.01));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).available(), dec!(100.01));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).total(), dec!(100.01));
     }
 
     #[test]
@@ -237,7 +348,7 @@ mod tests {
         let result = account.process_transaction(withdraw_tx);
 
         assert!(result.is_err());
-        assert!(account.available == dec!(100.0));
+        assert!(account.balance(DEFAULT_CURRENCY).available() == dec!(100.0));
     }
 
     #[test]
@@ -262,7 +373,7 @@ mod tests {
         let result = account.process_transaction(withdraw_tx);
 
         assert!(result.is_err());
-        assert!(account.available == dec!(100.0));
+        assert!(account.balance(DEFAULT_CURRENCY).available() == dec!(100.0));
     }
 
     #[test]
@@ -281,8 +392,8 @@ mod tests {
 
         let result = account.process_transaction(withdraw_tx);
 
-        assert_eq!(account.available, dec!(50.01));
-        assert_eq!(account.total, dec!(50.01));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).available(), dec!(50.01));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).total(), dec!(50.01));
         assert!(result.is_ok());
     }
 
@@ -343,29 +454,72 @@ mod tests {
         let result = account.process_transaction(tx);
 
         assert!(result.is_ok());
-        assert_eq!(account.available, dec!(-100.0));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).available(), dec!(-100.0));
     }
 
     #[test]
-    fn dispute_only_deposit_transactions() {
+    fn dispute_withdrawal_holds_reclaimed_outflow() {
         let mut account = Account::new(1);
-        let deposit_tx = Transaction::new(
-            1,
-            1,
-            TransactionType::Deposit {
-                amount: dec!(100.0),
-            },
-        );
-        account.process_transaction(deposit_tx).unwrap();
-
-        let withdraw_tx =
-            Transaction::new(1, 2, TransactionType::Withdrawal { amount: dec!(50.0) });
-        account.process_transaction(withdraw_tx).unwrap();
-        let tx = Transaction::new(1, 2, TransactionType::Dispute);
-
-        let result = account.process_transaction(tx);
+        account
+            .process_transaction(Transaction::new(
+                1,
+                1,
+                TransactionType::Deposit {
+                    amount: dec!(100.0),
+                },
+            ))
+            .unwrap();
+        account
+            .process_transaction(Transaction::new(
+                1,
+                2,
+                TransactionType::Withdrawal { amount: dec!(50.0) },
+            ))
+            .unwrap();
+
+        account
+            .process_transaction(Transaction::new(1, 2, TransactionType::Dispute))
+            .unwrap();
+
+        let balance = account.balance(DEFAULT_CURRENCY);
+        assert_eq!(balance.available(), dec!(50.0));
+        assert_eq!(balance.withheld(), dec!(50.0));
+        assert_eq!(balance.total(), dec!(100.0));
+        assert_eq!(balance.total(), balance.available() + balance.withheld());
+    }
 
-        assert!(result.is_err());
+    #[test]
+    fn chargeback_withdrawal_returns_funds_to_client() {
+        let mut account = Account::new(1);
+        account
+            .process_transaction(Transaction::new(
+                1,
+                1,
+                TransactionType::Deposit {
+                    amount: dec!(100.0),
+                },
+            ))
+            .unwrap();
+        account
+            .process_transaction(Transaction::new(
+                1,
+                2,
+                TransactionType::Withdrawal { amount: dec!(50.0) },
+            ))
+            .unwrap();
+        account
+            .process_transaction(Transaction::new(1, 2, TransactionType::Dispute))
+            .unwrap();
+
+        account
+            .process_transaction(Transaction::new(1, 2, TransactionType::Chargeback))
+            .unwrap();
+
+        let balance = account.balance(DEFAULT_CURRENCY);
+        assert_eq!(balance.available(), dec!(100.0));
+        assert_eq!(balance.withheld(), dec!(0.0));
+        assert_eq!(balance.total(), dec!(100.0));
+        assert!(account.locked());
     }
 
     #[test]
@@ -381,15 +535,15 @@ mod tests {
 
         account.process_transaction(deposit_tx).unwrap();
 
-        assert_eq!(account.available, dec!(100.0));
-        assert_eq!(account.withheld, dec!(0.0));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).available(), dec!(100.0));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).withheld(), dec!(0.0));
 
         let tx = Transaction::new(1, 1, TransactionType::Dispute);
 
         account.process_transaction(tx).unwrap();
 
-        assert_eq!(account.available, dec!(0.0));
-        assert_eq!(account.withheld, dec!(100.0));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).available(), dec!(0.0));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).withheld(), dec!(100.0));
     }
 
     #[test]
@@ -417,14 +571,40 @@ mod tests {
         let dispute_tx = Transaction::new(1, 1, TransactionType::Dispute);
         account.process_transaction(dispute_tx).unwrap();
 
-        assert_eq!(account.available, dec!(0.0));
-        assert_eq!(account.withheld, dec!(100.0));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).available(), dec!(0.0));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).withheld(), dec!(100.0));
 
         let resolve_tx = Transaction::new(1, 1, TransactionType::Resolve);
         account.process_transaction(resolve_tx).unwrap();
 
-        assert_eq!(account.available, dec!(100.0));
-        assert_eq!(account.withheld, dec!(0.0));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).available(), dec!(100.0));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).withheld(), dec!(0.0));
+    }
+
+    #[test]
+    fn resolve_allows_transaction_to_be_disputed_again() {
+        let mut account = Account::new(1);
+        let deposit_tx = Transaction::new(
+            1,
+            1,
+            TransactionType::Deposit {
+                amount: dec!(100.0),
+            },
+        );
+        account.process_transaction(deposit_tx).unwrap();
+
+        account
+            .process_transaction(Transaction::new(1, 1, TransactionType::Dispute))
+            .unwrap();
+        account
+            .process_transaction(Transaction::new(1, 1, TransactionType::Resolve))
+            .unwrap();
+
+        let result = account.process_transaction(Transaction::new(1, 1, TransactionType::Dispute));
+
+        assert!(result.is_ok());
+        assert_eq!(account.balance(DEFAULT_CURRENCY).available(), dec!(0.0));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).withheld(), dec!(100.0));
     }
 
     #[test]
@@ -452,14 +632,89 @@ mod tests {
         let dispute_tx = Transaction::new(1, 1, TransactionType::Dispute);
         account.process_transaction(dispute_tx).unwrap();
 
-        assert_eq!(account.total, dec!(100.0));
-        assert_eq!(account.withheld, dec!(100.0));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).total(), dec!(100.0));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).withheld(), dec!(100.0));
 
         let chargeback_tx = Transaction::new(1, 1, TransactionType::Chargeback);
         account.process_transaction(chargeback_tx).unwrap();
 
-        assert_eq!(account.total, dec!(0.0));
-        assert_eq!(account.withheld, dec!(0.0));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).total(), dec!(0.0));
+        assert_eq!(account.balance(DEFAULT_CURRENCY).withheld(), dec!(0.0));
+    }
+
+    #[test]
+    fn chargeback_is_terminal_and_blocks_redispute() {
+        let mut account = Account::new(1);
+        let deposit_tx = Transaction::new(
+            1,
+            1,
+            TransactionType::Deposit {
+                amount: dec!(100.0),
+            },
+        );
+        account.process_transaction(deposit_tx).unwrap();
+        account
+            .process_transaction(Transaction::new(1, 1, TransactionType::Dispute))
+            .unwrap();
+        account
+            .process_transaction(Transaction::new(1, 1, TransactionType::Chargeback))
+            .unwrap();
+
+        let result = account.process_transaction(Transaction::new(1, 1, TransactionType::Dispute));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn duplicate_deposit_id_is_rejected_and_original_preserved() {
+        let mut account = Account::new(1);
+        account
+            .process_transaction(Transaction::new(
+                1,
+                1,
+                TransactionType::Deposit {
+                    amount: dec!(100.0),
+                },
+            ))
+            .unwrap();
+
+        let replay = account.process_transaction(Transaction::new(
+            1,
+            1,
+            TransactionType::Deposit { amount: dec!(5.0) },
+        ));
+        assert_eq!(replay, Err(EngineError::DuplicateTransaction { tx: 1 }));
+
+        // The original amount must survive so a later dispute unwinds it.
+        account
+            .process_transaction(Transaction::new(1, 1, TransactionType::Dispute))
+            .unwrap();
+        assert_eq!(account.balance(DEFAULT_CURRENCY).withheld(), dec!(100.0));
+    }
+
+    #[test]
+    fn deposits_in_different_currencies_are_isolated() {
+        let mut account = Account::new(1);
+        account
+            .process_transaction(Transaction::new_in_currency(
+                1,
+                1,
+                "USD".to_string(),
+                TransactionType::Deposit { amount: dec!(100.0) },
+            ))
+            .unwrap();
+        account
+            .process_transaction(Transaction::new_in_currency(
+                1,
+                2,
+                "EUR".to_string(),
+                TransactionType::Deposit { amount: dec!(40.0) },
+            ))
+            .unwrap();
+
+        assert_eq!(account.balance("USD").available(), dec!(100.0));
+        assert_eq!(account.balance("EUR").available(), dec!(40.0));
+        assert_eq!(account.balance("GBP").available(), dec!(0.0));
     }
 
     #[test]
@@ -1,12 +1,13 @@
-use anyhow::Result;
 use rust_decimal::Decimal;
 
-use crate::models::account::Account;
+use crate::error::EngineError;
+use crate::models::account::{Account, CurrencyId, DEFAULT_CURRENCY};
 
 #[derive(Clone, Debug)]
 pub struct Transaction {
     client: u16,
     tx: u32,
+    currency: CurrencyId,
     transaction_type: TransactionType,
 }
 
@@ -21,9 +22,19 @@ pub enum TransactionType {
 
 impl Transaction {
     pub fn new(client: u16, tx: u32, transaction_type: TransactionType) -> Self {
+        Transaction::new_in_currency(client, tx, DEFAULT_CURRENCY.to_string(), transaction_type)
+    }
+
+    pub fn new_in_currency(
+        client: u16,
+        tx: u32,
+        currency: CurrencyId,
+        transaction_type: TransactionType,
+    ) -> Self {
         Transaction {
             client,
             tx,
+            currency,
             transaction_type,
         }
     }
@@ -36,6 +47,10 @@ impl Transaction {
         self.tx
     }
 
+    pub fn currency(&self) -> &str {
+        &self.currency
+    }
+
     pub fn transaction_type(&self) -> &TransactionType {
         &self.transaction_type
     }
@@ -48,13 +63,13 @@ impl Transaction {
         }
     }
 
-    pub fn run(&self, account: &mut Account) -> Result<()> {
+    pub fn run(&self, account: &mut Account) -> Result<(), EngineError> {
         match self.transaction_type {
             TransactionType::Deposit { amount } => {
-                account.deposit(amount)?;
+                account.deposit(&self.currency, amount)?;
             }
             TransactionType::Withdrawal { amount } => {
-                account.withdraw(amount)?;
+                account.withdraw(&self.currency, amount)?;
             }
             TransactionType::Dispute => {
                 account.dispute(self)?;
@@ -2,6 +2,7 @@ use anyhow::{anyhow, bail};
 use rust_decimal::Decimal;
 use serde::Deserialize;
 
+use crate::models::account::DEFAULT_CURRENCY;
 use crate::models::transaction::{Transaction, TransactionType};
 
 #[derive(Clone, Debug, Deserialize)]
@@ -11,16 +12,24 @@ pub struct TransactionRecord {
     pub client: u16,
     pub tx: u32,
     pub amount: Option<f64>,
+    #[serde(default)]
+    pub currency: Option<String>,
 }
 
 impl TryFrom<TransactionRecord> for Transaction {
     type Error = anyhow::Error;
 
     fn try_from(record: TransactionRecord) -> Result<Self, Self::Error> {
+        let currency = record
+            .currency
+            .clone()
+            .unwrap_or_else(|| DEFAULT_CURRENCY.to_string());
+
         match record.transaction_type.as_str() {
-            "deposit" => Ok(Transaction::new(
+            "deposit" => Ok(Transaction::new_in_currency(
                 record.client,
                 record.tx,
+                currency,
                 TransactionType::Deposit {
                     amount: Decimal::from_f64_retain(
                         record
@@ -30,9 +39,10 @@ impl TryFrom<TransactionRecord> for Transaction {
                     .ok_or_else(|| anyhow!("Invalid amount for deposit"))?,
                 },
             )),
-            "withdrawal" => Ok(Transaction::new(
+            "withdrawal" => Ok(Transaction::new_in_currency(
                 record.client,
                 record.tx,
+                currency,
                 TransactionType::Withdrawal {
                     amount: Decimal::from_f64_retain(
                         record
@@ -42,19 +52,22 @@ impl TryFrom<TransactionRecord> for Transaction {
                     .ok_or_else(|| anyhow!("Invalid amount for withdrawal"))?,
                 },
             )),
-            "dispute" => Ok(Transaction::new(
+            "dispute" => Ok(Transaction::new_in_currency(
                 record.client,
                 record.tx,
+                currency,
                 TransactionType::Dispute,
             )),
-            "resolve" => Ok(Transaction::new(
+            "resolve" => Ok(Transaction::new_in_currency(
                 record.client,
                 record.tx,
+                currency,
                 TransactionType::Resolve,
             )),
-            "chargeback" => Ok(Transaction::new(
+            "chargeback" => Ok(Transaction::new_in_currency(
                 record.client,
                 record.tx,
+                currency,
                 TransactionType::Chargeback,
             )),
             other => bail!("Unknown transaction type: {}", other),
@@ -1,6 +1,10 @@
+use std::sync::mpsc;
+use std::thread;
+
 use anyhow::Result;
 use csv::{ReaderBuilder, Trim, Writer};
 
+use crate::models::transaction::Transaction;
 use crate::{engine::Engine, models::transaction_record::TransactionRecord};
 
 pub struct FileRunner;
@@ -10,25 +14,27 @@ impl FileRunner {
         FileRunner
     }
 
-    pub fn run(&self, input_file: &str, engine: &mut Engine) -> Result<()> {
-        let mut csv_reader = ReaderBuilder::new().trim(Trim::All).from_path(input_file)?;
+    pub fn run(&self, input_file: &str, engine: &mut Engine, workers: usize) -> Result<()> {
+        let file = std::fs::File::open(input_file)?;
+        self.run_reader(file, engine, workers)
+    }
 
-        for result in csv_reader.deserialize::<TransactionRecord>() {
-            let record = match result {
-                Ok(r) => r,
-                Err(_) => {
-                    continue;
-                }
-            };
-            let transaction = match record.try_into() {
-                Ok(t) => t,
-                Err(_) => {
+    pub fn run_reader<R: std::io::Read>(
+        &self,
+        reader: R,
+        engine: &mut Engine,
+        workers: usize,
+    ) -> Result<()> {
+        let mut csv_reader = ReaderBuilder::new().trim(Trim::All).from_reader(reader);
+
+        if workers <= 1 {
+            for transaction in parse_transactions(&mut csv_reader) {
+                if engine.process_transaction(transaction).is_err() {
                     continue;
                 }
-            };
-            if let Err(_) = engine.process_transaction(transaction) {
-                continue;
             }
+        } else {
+            self.run_sharded(&mut csv_reader, engine, workers);
         }
 
         self.print_accounts(engine)?;
@@ -36,23 +42,73 @@ impl FileRunner {
         Ok(())
     }
 
+    /// Route each parsed transaction to one of `workers` shards by
+    /// `client % workers`. A client's transactions always land in the same
+    /// shard and keep their arrival order, so a dispute never overtakes the
+    /// deposit it targets. Each worker owns a disjoint set of accounts and
+    /// processes its queue without locking; their account maps are merged back
+    /// into `engine` once the input is exhausted.
+    fn run_sharded<R: std::io::Read>(
+        &self,
+        csv_reader: &mut csv::Reader<R>,
+        engine: &mut Engine,
+        workers: usize,
+    ) {
+        let mut senders = Vec::with_capacity(workers);
+        let mut handles = Vec::with_capacity(workers);
+
+        for _ in 0..workers {
+            let (tx, rx) = mpsc::channel::<Transaction>();
+            senders.push(tx);
+            handles.push(thread::spawn(move || {
+                let mut shard = Engine::new();
+                for transaction in rx {
+                    let _ = shard.process_transaction(transaction);
+                }
+                shard.into_accounts()
+            }));
+        }
+
+        for transaction in parse_transactions(csv_reader) {
+            let shard = (transaction.client() as usize) % workers;
+            // The receiver only drops after we join, so a send cannot fail.
+            let _ = senders[shard].send(transaction);
+        }
+
+        drop(senders);
+
+        for handle in handles {
+            if let Ok(accounts) = handle.join() {
+                for account in accounts.into_values() {
+                    engine.insert_account(account);
+                }
+            }
+        }
+    }
+
     fn print_accounts(&self, engine: &Engine) -> Result<()> {
         let accounts = engine.get_accounts();
         let mut sorted_accounts: Vec<_> = accounts.values().collect();
-        sorted_accounts.sort_by_key(|a| a.client);
+        sorted_accounts.sort_by_key(|a| a.client());
 
         let mut wtr = Writer::from_writer(std::io::stdout());
 
-        wtr.write_record(&["client", "available", "held", "total", "locked"])?;
+        wtr.write_record(&["client", "currency", "available", "held", "total", "locked"])?;
 
         for account in sorted_accounts {
-            wtr.write_record(&[
-                account.client.to_string(),
-                format!("{:.4}", account.available),
-                format!("{:.4}", account.withheld),
-                format!("{:.4}", account.total),
-                account.locked.to_string(),
-            ])?;
+            let mut balances: Vec<_> = account.balances().iter().collect();
+            balances.sort_by(|a, b| a.0.cmp(b.0));
+
+            for (currency, balance) in balances {
+                wtr.write_record(&[
+                    account.client().to_string(),
+                    currency.to_string(),
+                    format!("{:.4}", balance.available()),
+                    format!("{:.4}", balance.withheld()),
+                    format!("{:.4}", balance.total()),
+                    account.locked().to_string(),
+                ])?;
+            }
         }
 
         wtr.flush()?;
@@ -60,3 +116,14 @@ impl FileRunner {
         Ok(())
     }
 }
+
+/// Deserialize each CSV row into a [`Transaction`], silently dropping rows that
+/// fail to parse or convert, mirroring the serial loop's skip-on-error policy.
+fn parse_transactions<'r, R: std::io::Read>(
+    csv_reader: &'r mut csv::Reader<R>,
+) -> impl Iterator<Item = Transaction> + 'r {
+    csv_reader
+        .deserialize::<TransactionRecord>()
+        .filter_map(|result| result.ok())
+        .filter_map(|record| Transaction::try_from(record).ok())
+}